@@ -43,84 +43,219 @@
 //!
 //! Any children passed to the helmet component will then be placed in the `<head></head>` of your document.
 //!
-//! They will be visible while the component is rendered. Duplicates **won't** get appended multiple times.
+//! They will be visible while the component is rendered. Duplicates **won't** get appended multiple times,
+//! and are reference-counted so an element shared by several mounted `Helmet`s is only removed once the
+//! last one unmounts.
+//!
+//! Children aren't limited to a single text node: multiple text fragments and nested elements are
+//! serialized together, so e.g. a `script { "..." "..." }` JSON-LD block or a `noscript` wrapping
+//! several elements works as expected.
+//!
+//! `html {}` and `body {}` are handled specially: instead of being appended as new elements, their
+//! attributes (e.g. `lang`, `dir`, theme classes) are applied directly onto `document.documentElement()`
+//! / `document.body()`. Once no mounted `Helmet` sets an attribute anymore, it is restored to the
+//! value it had before (or removed, if it had none).
+//!
+//! ## Server-side rendering
+//! When rendered off the browser (e.g. through `dioxus-ssr`), `Helmet` can't reach `web_sys::window()`,
+//! so mounted elements are instead collected and can be pulled out with [`ssr::render_head`] to splice
+//! into the server response's `<head>`.
 
 use dioxus::prelude::*;
-use dioxus_core::AttributeValue;
+use dioxus_core::{AttributeValue, DynamicNode};
 use lazy_static::lazy_static;
 use rustc_hash::FxHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 use std::sync::Mutex;
 
+pub mod ssr;
+
 lazy_static! {
-    static ref INIT_CACHE: Mutex<Vec<u64>> = Mutex::new(Vec::new());
+    /// Hash of each mounted element mapped to how many currently-mounted `Helmet`s emit it.
+    /// The DOM node is only created when a count transitions `0 -> 1`, and only removed
+    /// when it drops back to `0`, so shared elements survive partial unmounts.
+    static ref INIT_CACHE: Mutex<HashMap<u64, usize>> = Mutex::new(HashMap::new());
 }
 
 #[allow(non_snake_case)]
 #[component]
 pub fn Helmet(children: Element) -> Element {
-    use_hook_with_cleanup(move || {
-        let document = web_sys::window()?.document()?;
-        let head = document.head()?;
-        let element_maps = extract_element_maps(&children)?;
-        let mut init_cache = INIT_CACHE.try_lock().ok()?;
-
-        element_maps.iter().for_each(|element_map| {
-            let mut hasher = FxHasher::default();
-            element_map.hash(&mut hasher);
-            let hash = hasher.finish();
-
-            if init_cache.contains(&hash) { return; }
-            init_cache.push(hash);
-
-            if let Some(new_element) = element_map.try_into_element(&document, &hash) {
-                let _ = head.append_child(&new_element);
+    use_hook_with_cleanup(
+        move || {
+            let element_maps = extract_element_maps(&children)?;
+            mount(&element_maps)?;
+            Some(element_maps)
+        },
+        move |element_maps| {
+            let Some(element_maps) = element_maps else { return; };
+            unmount(&element_maps);
+        },
+    );
+
+    None
+}
+
+fn hash_element_map(element_map: &ElementMap) -> u64 {
+    let mut hasher = FxHasher::default();
+    element_map.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[cfg(target_arch = "wasm32")]
+fn mount(element_maps: &[ElementMap]) -> Option<()> {
+    let document = web_sys::window()?.document()?;
+    let head = document.head()?;
+    let mut init_cache = INIT_CACHE.try_lock().ok()?;
+
+    seed_init_cache(&document, &mut init_cache);
+
+    element_maps.iter().for_each(|element_map| {
+        let hash = hash_element_map(element_map);
+
+        let count = init_cache.entry(hash).or_insert(0);
+        *count += 1;
+        if *count > 1 { return; }
+
+        match element_map.target {
+            ElementTarget::Child => {
+                if element_exists(&document, hash) { return; }
+
+                if let Some(new_element) = element_map.try_into_element(&document, &hash) {
+                    let _ = head.append_child(&new_element);
+                }
             }
+            ElementTarget::HtmlRoot | ElementTarget::BodyRoot => {
+                if let Some(root) = element_map.target_element(&document) {
+                    apply_root_attributes(element_map, &root, hash);
+                }
+            }
+        }
+    });
+
+    Some(())
+}
+
+/// Seeds [`INIT_CACHE`] with the hashes of `[data-helmet-id]` elements already present in the
+/// document (i.e. elements that were server-rendered), so the first client mount hydrates instead
+/// of appending duplicates. Runs at most once per page load.
+#[cfg(target_arch = "wasm32")]
+fn seed_init_cache(document: &web_sys::Document, init_cache: &mut HashMap<u64, usize>) {
+    static SEEDED: std::sync::Once = std::sync::Once::new();
+
+    SEEDED.call_once(|| {
+        let Ok(existing) = document.query_selector_all("[data-helmet-id]") else { return; };
+        let Ok(Some(existing_iter)) = js_sys::try_iter(&existing) else { return; };
+
+        existing_iter.for_each(|node| {
+            let Ok(node) = node else { return; };
+            let element = web_sys::Element::from(node);
+            let Some(hash) = element
+                .get_attribute("data-helmet-id")
+                .and_then(|hash| hash.parse::<u64>().ok())
+            else { return; };
+
+            // `0` marks existence only; the owning `mount` call still takes it `0 -> 1`
+            // (skipping the append via `element_exists`), so `unmount` can bring a
+            // server-rendered element back to `0` and actually remove it.
+            init_cache.entry(hash).or_insert(0);
         });
+    });
+}
 
-        Some(element_maps)
-    },
-    move |element_maps| {
-        let Some(element_maps) = element_maps else { return; };
-        let Some(window) = web_sys::window() else { return; };
-        let Some(document) = window.document() else { return; };
-        let Ok(mut init_cache) = INIT_CACHE.try_lock() else { return; };
-
-        element_maps.iter().for_each(|element_map| {
-            let mut hasher = FxHasher::default();
-            element_map.hash(&mut hasher);
-            let hash = hasher.finish();
-
-            if let Some(index) = init_cache.iter().position(|&c| c == hash) {
-                init_cache.remove(index);
-            }
+#[cfg(target_arch = "wasm32")]
+fn element_exists(document: &web_sys::Document, hash: u64) -> bool {
+    document
+        .query_selector(&format!("[data-helmet-id='{hash}']"))
+        .ok()
+        .flatten()
+        .is_some()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn mount(element_maps: &[ElementMap]) -> Option<()> {
+    // `html`/`body` roots apply attributes to an existing element rather than
+    // rendering markup, so they have no representation in a `<head>` string.
+    let hashed = element_maps
+        .iter()
+        .filter(|element_map| element_map.target == ElementTarget::Child)
+        .map(|element_map| (hash_element_map(element_map), element_map.clone()))
+        .collect::<Vec<_>>();
+
+    ssr::register(&hashed);
 
-            if let Ok(children) =
-            document.query_selector_all(&format!("[data-helmet-id='{hash}']"))
-            {
-                if let Ok(Some(children_iter)) = js_sys::try_iter(&children) {
-                    children_iter.for_each(|child| {
-                        if let Ok(child) = child {
-                            let el = web_sys::Element::from(child);
-                            el.remove();
-                        };
-                    });
+    Some(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn unmount(element_maps: &[ElementMap]) {
+    let Some(window) = web_sys::window() else { return; };
+    let Some(document) = window.document() else { return; };
+    let Ok(mut init_cache) = INIT_CACHE.try_lock() else { return; };
+
+    element_maps.iter().for_each(|element_map| {
+        let hash = hash_element_map(element_map);
+
+        let Some(count) = init_cache.get_mut(&hash) else { return; };
+        *count -= 1;
+        if *count > 0 { return; }
+        init_cache.remove(&hash);
+
+        match element_map.target {
+            ElementTarget::Child => {
+                if let Ok(children) =
+                document.query_selector_all(&format!("[data-helmet-id='{hash}']"))
+                {
+                    if let Ok(Some(children_iter)) = js_sys::try_iter(&children) {
+                        children_iter.for_each(|child| {
+                            if let Ok(child) = child {
+                                let el = web_sys::Element::from(child);
+                                el.remove();
+                            };
+                        });
+                    }
                 }
             }
-        });
+            ElementTarget::HtmlRoot | ElementTarget::BodyRoot => {
+                if let Some(root) = element_map.target_element(&document) {
+                    restore_root_attributes(&root, element_map.target, hash);
+                }
+            }
+        }
     });
+}
 
-    None
+#[cfg(not(target_arch = "wasm32"))]
+fn unmount(_element_maps: &[ElementMap]) {}
+
+/// Where an [`ElementMap`] ends up: appended as a fresh child of `<head>`, or applied
+/// as attributes onto an existing root element (`<html>`/`<body>`).
+#[derive(Debug, Hash, Clone, Copy, PartialEq, Eq)]
+enum ElementTarget {
+    Child,
+    HtmlRoot,
+    BodyRoot,
 }
 
 #[derive(Debug, Hash, Clone)]
 struct ElementMap {
     tag: &'static str,
     attributes: Vec<(&'static str, String)>,
-    inner_html: Option<&'static str>,
+    inner_html: Option<String>,
+    target: ElementTarget,
 }
 
 impl ElementMap {
+    #[cfg(target_arch = "wasm32")]
+    fn target_element(&self, document: &web_sys::Document) -> Option<web_sys::Element> {
+        match self.target {
+            ElementTarget::HtmlRoot => document.document_element(),
+            ElementTarget::BodyRoot => document.body().map(Into::into),
+            ElementTarget::Child => None,
+        }
+    }
+
+    #[cfg(target_arch = "wasm32")]
     fn try_into_element(
         &self,
         document: &web_sys::Document,
@@ -133,16 +268,139 @@ impl ElementMap {
         }).ok()?;
         new_element.set_attribute("data-helmet-id", &hash.to_string()).ok()?;
 
-        if let Some(inner_html) = self.inner_html {
+        if let Some(inner_html) = &self.inner_html {
             new_element.set_inner_html(inner_html);
         }
 
         Some(new_element)
     }
+
+    pub(crate) fn to_markup(&self, hash: u64) -> String {
+        let mut markup = format!("<{}", self.tag);
+
+        self.attributes.iter().for_each(|(name, value)| {
+            markup.push_str(&format!(" {name}=\"{}\"", escape_attribute(value)));
+        });
+        markup.push_str(&format!(" data-helmet-id=\"{hash}\">"));
+
+        // Void elements (https://html.spec.whatwg.org/#void-elements) have no closing tag
+        // and can't have children, so `link`/`meta`/etc. must not get a matching `</tag>`.
+        if VOID_ELEMENTS.contains(&self.tag) {
+            return markup;
+        }
+
+        if let Some(inner_html) = &self.inner_html {
+            markup.push_str(inner_html);
+        }
+
+        markup.push_str(&format!("</{}>", self.tag));
+        markup
+    }
+}
+
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+fn escape_attribute(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;")
+}
+
+lazy_static! {
+    /// Attributes every currently-mounted `Helmet` wants applied to a given root, in mount
+    /// order, per [`ElementTarget`]. Recomputed into the DOM as a whole on every mount/unmount
+    /// (see [`recompute_root_attributes`]), rather than saved/restored per hash, so two
+    /// `Helmet`s setting the same attribute name to different values (e.g. `body { class: "a" }`
+    /// and `body { class: "b" }`) don't clobber each other on a partial unmount — the
+    /// most-recently-mounted value for a name always wins while any setter of it remains mounted.
+    static ref ROOT_ATTRS: Mutex<HashMap<ElementTarget, Vec<(u64, Vec<(String, String)>)>>> =
+        Mutex::new(HashMap::new());
+
+    /// The value (if any) each attribute name had on a root before any `Helmet` touched it,
+    /// captured lazily the first time that name is set, so it can be restored once no
+    /// mounted `Helmet` sets it anymore.
+    static ref ROOT_ATTR_BASELINE: Mutex<HashMap<ElementTarget, HashMap<String, Option<String>>>> =
+        Mutex::new(HashMap::new());
+}
+
+/// Records `element_map`'s attributes as `hash`'s contribution to its root target, then
+/// reapplies the merged result onto `root`.
+#[cfg(target_arch = "wasm32")]
+fn apply_root_attributes(element_map: &ElementMap, root: &web_sys::Element, hash: u64) {
+    let Ok(mut root_attrs) = ROOT_ATTRS.try_lock() else { return; };
+    let Ok(mut baseline) = ROOT_ATTR_BASELINE.try_lock() else { return; };
+
+    let attrs: Vec<(String, String)> = element_map.attributes.iter()
+        .map(|(name, value)| (name.to_string(), value.clone()))
+        .collect();
+
+    let target_baseline = baseline.entry(element_map.target).or_default();
+    attrs.iter().for_each(|(name, _)| {
+        target_baseline.entry(name.clone()).or_insert_with(|| root.get_attribute(name));
+    });
+
+    root_attrs.entry(element_map.target).or_default().push((hash, attrs));
+
+    recompute_root_attributes(root, element_map.target, &root_attrs, &baseline);
+}
+
+/// Drops `hash`'s contribution to its root target, then reapplies the merged result of the
+/// remaining mounted `Helmet`s (restoring the pre-`Helmet` baseline for any attribute none of
+/// them set anymore).
+#[cfg(target_arch = "wasm32")]
+fn restore_root_attributes(root: &web_sys::Element, target: ElementTarget, hash: u64) {
+    let Ok(mut root_attrs) = ROOT_ATTRS.try_lock() else { return; };
+    let Ok(baseline) = ROOT_ATTR_BASELINE.try_lock() else { return; };
+
+    if let Some(entries) = root_attrs.get_mut(&target) {
+        entries.retain(|(entry_hash, _)| *entry_hash != hash);
+    }
+
+    recompute_root_attributes(root, target, &root_attrs, &baseline);
+}
+
+#[cfg(target_arch = "wasm32")]
+fn recompute_root_attributes(
+    root: &web_sys::Element,
+    target: ElementTarget,
+    root_attrs: &HashMap<ElementTarget, Vec<(u64, Vec<(String, String)>)>>,
+    baseline: &HashMap<ElementTarget, HashMap<String, Option<String>>>,
+) {
+    let Some(target_baseline) = baseline.get(&target) else { return; };
+
+    // Later-mounted entries win for attribute names more than one of them sets.
+    let mut merged: HashMap<&str, &str> = HashMap::new();
+    root_attrs.get(&target).into_iter().flatten().for_each(|(_, attrs)| {
+        attrs.iter().for_each(|(name, value)| {
+            merged.insert(name, value);
+        });
+    });
+
+    target_baseline.iter().for_each(|(name, original_value)| {
+        match merged.get(name.as_str()) {
+            Some(value) => { let _ = root.set_attribute(name, value); }
+            None => match original_value {
+                Some(value) => { let _ = root.set_attribute(name, value); }
+                None => { let _ = root.remove_attribute(name); }
+            },
+        }
+    });
+}
+
+/// Stringifies an [`AttributeValue`], or `None` for variants with no static representation
+/// (a listener, an opaque `Any`, or no value at all).
+fn dynamic_attribute_value(value: &AttributeValue) -> Option<String> {
+    match value {
+        AttributeValue::Bool(v) => Some(v.to_string()),
+        AttributeValue::Float(v) => Some(v.to_string()),
+        AttributeValue::Int(v) => Some(v.to_string()),
+        AttributeValue::Text(v) => Some(v.to_string()),
+        AttributeValue::None | AttributeValue::Listener(_) | AttributeValue::Any(_) => None,
+    }
 }
 
 fn extract_element_maps(children: &Element) -> Option<Vec<ElementMap>> {
-    use AttributeValue as AV;
     use TemplateAttribute as TA;
     use TemplateNode as TN;
 
@@ -160,34 +418,146 @@ fn extract_element_maps(children: &Element) -> Option<Vec<ElementMap>> {
                 .for_each(|attr| match attr {
                     TA::Static { name, value, .. } => attributes.push((*name, value.to_string())),
                     TA::Dynamic { id } => vnode.dynamic_attrs[*id].iter().for_each(|attr| {
-                        match &attr.value {
-                            AV::Bool(v) => attributes.push((attr.name, v.to_string())),
-                            AV::Float(v) => attributes.push((attr.name, v.to_string())),
-                            AV::Int(v) => attributes.push((attr.name, v.to_string())),
-                            AV::Text(v) => attributes.push((attr.name, v.to_string())),
-                            AV::None | AV::Listener(_) | AV::Any(_) => {}
+                        if let Some(value) = dynamic_attribute_value(&attr.value) {
+                            attributes.push((attr.name, value));
                         }
                     })
                 });
 
-            let inner_html = match children.first() {
-                Some(TN::Text { text }) => Some(*text),
-                Some(TN::Element { children, .. }) if children.len() == 1 => {
-                    match children.first() {
-                        Some(TN::Text { text }) => Some(*text),
-                        _ => None,
-                    }
-                }
-                _ => None,
+            let inner_html = if children.is_empty() {
+                None
+            } else {
+                Some(render_children(children, vnode))
+            };
+
+            let target = match *tag {
+                "html" => ElementTarget::HtmlRoot,
+                "body" => ElementTarget::BodyRoot,
+                _ => ElementTarget::Child,
             };
 
             Some(ElementMap {
                 tag,
                 attributes,
-                inner_html
+                inner_html,
+                target,
             })
         })
         .collect();
 
     Some(elements)
 }
+
+/// Recursively serializes a list of child [`TemplateNode`]s into HTML, so a `Helmet`
+/// child can contain multiple text fragments and/or nested elements (e.g. a JSON-LD
+/// `script` body, or a `noscript` wrapping several elements) rather than just one
+/// leading text node. `vnode` resolves `DynamicText` nodes, and dynamic attributes the
+/// same way [`extract_element_maps`] resolves them for root elements; `Dynamic` nodes are
+/// dropped.
+fn render_children(children: &[TemplateNode], vnode: &VNode) -> String {
+    children.iter().map(|node| render_node(node, vnode)).collect()
+}
+
+fn render_node(node: &TemplateNode, vnode: &VNode) -> String {
+    match node {
+        TemplateNode::Text { text } => text.to_string(),
+        TemplateNode::DynamicText { id } => match &vnode.dynamic_nodes[*id] {
+            DynamicNode::Text(text) => text.value.to_string(),
+            // A dynamic node resolving to a component/fragment/placeholder has no static
+            // text representation here, so it contributes nothing rather than panicking.
+            _ => String::new(),
+        },
+        TemplateNode::Element { tag, attrs, children, .. } => {
+            let mut markup = format!("<{tag}");
+
+            attrs.iter().for_each(|attr| match attr {
+                TemplateAttribute::Static { name, value, .. } => {
+                    markup.push_str(&format!(" {name}=\"{}\"", escape_attribute(value)));
+                }
+                TemplateAttribute::Dynamic { id } => vnode.dynamic_attrs[*id].iter().for_each(|attr| {
+                    if let Some(value) = dynamic_attribute_value(&attr.value) {
+                        markup.push_str(&format!(" {}=\"{}\"", attr.name, escape_attribute(&value)));
+                    }
+                }),
+            });
+            markup.push('>');
+
+            // Void elements (e.g. a fallback `img`/`link`/`meta`) have no closing tag and
+            // can't have children, mirroring the same rule `ElementMap::to_markup` applies.
+            if VOID_ELEMENTS.contains(tag) {
+                return markup;
+            }
+
+            markup.push_str(&render_children(children, vnode));
+            markup.push_str(&format!("</{tag}>"));
+
+            markup
+        }
+        // A `Dynamic` node (component, fragment, or conditional branch) can't be statically
+        // flattened into the parent's inner HTML without executing it, so it's dropped.
+        TemplateNode::Dynamic { .. } => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dioxus_core::RenderReturn;
+
+    /// Returns the root `VNode` of an already-rebuilt `dom`, so tests can exercise `render_children`
+    /// against a real template (including any dynamic attrs/nodes it resolves).
+    fn root_vnode(dom: &VirtualDom) -> &VNode {
+        match dom.base_scope().root_node() {
+            RenderReturn::Ready(vnode) => vnode,
+            _ => panic!("component did not render"),
+        }
+    }
+
+    fn root_children(vnode: &VNode) -> &'static [TemplateNode<'static>] {
+        let template = vnode.template.get();
+        match &template.roots[0] {
+            TemplateNode::Element { children, .. } => *children,
+            _ => panic!("expected the root node to be an element"),
+        }
+    }
+
+    #[test]
+    fn renders_a_multi_fragment_script_body() {
+        let mut dom = VirtualDom::new(|cx| {
+            cx.render(rsx! {
+                script {
+                    r#"{{"@context":"https://schema.org","#
+                    r#""@type":"Organization"}}"#
+                }
+            })
+        });
+        dom.rebuild();
+
+        let vnode = root_vnode(&dom);
+
+        assert_eq!(
+            render_children(root_children(vnode), vnode),
+            r#"{"@context":"https://schema.org","@type":"Organization"}"#
+        );
+    }
+
+    #[test]
+    fn renders_a_nested_element_subtree_with_dynamic_attributes() {
+        let mut dom = VirtualDom::new(|cx| {
+            let href = String::from("/fallback");
+            cx.render(rsx! {
+                noscript {
+                    a { href: "{href}", "enable JavaScript" }
+                }
+            })
+        });
+        dom.rebuild();
+
+        let vnode = root_vnode(&dom);
+
+        assert_eq!(
+            render_children(root_children(vnode), vnode),
+            r#"<a href="/fallback">enable JavaScript</a>"#
+        );
+    }
+}
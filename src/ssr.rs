@@ -0,0 +1,126 @@
+//! Server-side rendering support for [`Helmet`](crate::Helmet).
+//!
+//! Off the browser (e.g. when rendered through `dioxus-ssr`), `Helmet` can't reach
+//! `web_sys::window()`, so mounted elements are instead collected into a thread-local
+//! registry, in the order they were first mounted.
+//!
+//! A server using this module MUST call exactly one of [`render_head`] or [`clear`] per
+//! render on every thread that might run one: `render_head` drains the registry as part of
+//! serializing it, but a render that errors out (or a caller that simply forgets) before
+//! reaching `render_head` would otherwise leak its head tags into the next request served
+//! by that thread, since threads are commonly reused by a pool.
+//!
+//! ```ignore
+//! dom.rebuild();
+//! match dioxus_ssr::render(&dom) {
+//!     Ok(body) => format!("<head>{}</head>{body}", dioxus_helmet::ssr::render_head()),
+//!     Err(err) => {
+//!         dioxus_helmet::ssr::clear();
+//!         return Err(err);
+//!     }
+//! }
+//! ```
+
+use crate::ElementMap;
+use std::cell::RefCell;
+
+thread_local! {
+    static HEAD_REGISTRY: RefCell<Vec<(u64, ElementMap)>> = RefCell::new(Vec::new());
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) fn register(element_maps: &[(u64, ElementMap)]) {
+    HEAD_REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        element_maps.iter().for_each(|(hash, element_map)| {
+            // Mirrors the client's dedup-by-hash: the first mount to emit an element wins,
+            // and mount order is preserved so `render_head`'s output is stable/diffable.
+            if !registry.iter().any(|(existing_hash, _)| existing_hash == hash) {
+                registry.push((*hash, element_map.clone()));
+            }
+        });
+    });
+}
+
+/// Serializes every `Helmet`-managed element registered on the current thread, in the order
+/// they were first mounted, into `<head>`-ready markup, then clears the registry so the next
+/// render on this thread starts fresh. See the [module docs](self) for the must-call contract.
+pub fn render_head() -> String {
+    HEAD_REGISTRY.with(|registry| {
+        registry
+            .borrow_mut()
+            .drain(..)
+            .map(|(hash, element_map)| element_map.to_markup(hash))
+            .collect()
+    })
+}
+
+/// Discards any elements registered on the current thread without serializing them. Use this
+/// on a render's error path (or defensively at the start of handling a request) when
+/// [`render_head`] won't be reached, so state can't leak into the next render on this thread.
+pub fn clear() {
+    HEAD_REGISTRY.with(|registry| registry.borrow_mut().clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ElementTarget;
+
+    fn element_map(tag: &'static str, attributes: Vec<(&'static str, &str)>) -> ElementMap {
+        ElementMap {
+            tag,
+            attributes: attributes.into_iter().map(|(name, value)| (name, value.to_string())).collect(),
+            inner_html: None,
+            target: ElementTarget::Child,
+        }
+    }
+
+    #[test]
+    fn serializes_void_elements_without_a_closing_tag() {
+        let link = element_map("link", vec![("rel", "icon"), ("href", "/favicon.ico")]);
+
+        assert_eq!(
+            link.to_markup(1),
+            r#"<link rel="icon" href="/favicon.ico" data-helmet-id="1">"#
+        );
+    }
+
+    #[test]
+    fn escapes_quotes_and_ampersands_in_attribute_values() {
+        let anchor = element_map("a", vec![("href", "/search?q=\"a\"&b")]);
+
+        assert_eq!(
+            anchor.to_markup(2),
+            r#"<a href="/search?q=&quot;a&quot;&amp;b" data-helmet-id="2"></a>"#
+        );
+    }
+
+    #[test]
+    fn register_dedups_by_hash_and_preserves_mount_order() {
+        clear();
+
+        let title = element_map("title", vec![]);
+        let meta = element_map("meta", vec![("name", "description")]);
+        let replacement_title = element_map("title", vec![("data-changed", "true")]);
+
+        register(&[(1, title.clone()), (2, meta.clone())]);
+        // Same hash as `title`: the first-mounted element map wins, not this one.
+        register(&[(1, replacement_title)]);
+
+        assert_eq!(
+            render_head(),
+            format!("{}{}", title.to_markup(1), meta.to_markup(2))
+        );
+    }
+
+    #[test]
+    fn render_head_clears_the_registry() {
+        clear();
+        register(&[(1, element_map("title", vec![]))]);
+
+        render_head();
+
+        assert_eq!(render_head(), "");
+    }
+}